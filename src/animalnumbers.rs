@@ -0,0 +1,34 @@
+const ANIMALS: &[&str] = &[
+    "ant", "bat", "cat", "dog", "eel", "fox", "gnu", "hen", "ibis", "jay", "kite", "lion", "mole",
+    "newt", "owl", "pig", "quail", "rat", "seal", "toad", "urchin", "vole", "wasp", "yak", "zebra",
+];
+
+/// Encodes a numeric pasta id as a `-`-separated sequence of animal names.
+pub fn to_animal_names(mut id: u64) -> String {
+    let base = ANIMALS.len() as u64;
+    let mut parts = Vec::new();
+
+    loop {
+        parts.push(ANIMALS[(id % base) as usize]);
+        id /= base;
+        if id == 0 {
+            break;
+        }
+    }
+
+    parts.reverse();
+    parts.join("-")
+}
+
+/// Decodes a `-`-separated sequence of animal names back into the numeric pasta id.
+pub fn to_u64(names: &str) -> u64 {
+    let base = ANIMALS.len() as u64;
+    let mut id: u64 = 0;
+
+    for part in names.split('-') {
+        let index = ANIMALS.iter().position(|a| *a == part).unwrap_or(0) as u64;
+        id = id * base + index;
+    }
+
+    id
+}
@@ -0,0 +1,27 @@
+use std::fs;
+use std::io;
+
+use crate::pasta::Pasta;
+
+const DATABASE_PATH: &str = "./pasta_data/database.json";
+
+pub fn save_to_file(pastas: &Vec<Pasta>) {
+    let serialized = serde_json::to_string(pastas).expect("failed to serialize pastas");
+    fs::write(DATABASE_PATH, serialized).expect("failed to write database file");
+}
+
+pub fn load_from_file() -> io::Result<Vec<Pasta>> {
+    let contents = match fs::read_to_string(DATABASE_PATH) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    serde_json::from_str(&contents).map_err(|err| {
+        log::error!(
+            "Failed to parse {}, refusing to start with a truncated pasta list: {}",
+            DATABASE_PATH,
+            err
+        );
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    })
+}
@@ -0,0 +1,51 @@
+/// Encodes/decodes pasta ids into short, collision-free, reversible slugs.
+///
+/// This isn't the full `hashids.org` spec, just its core trick: XOR the id
+/// with a salt-derived offset, then base-N encode the result using a
+/// configurable alphabet.
+pub struct HashId {
+    alphabet: Vec<char>,
+    offset: u64,
+}
+
+impl HashId {
+    pub fn new(alphabet: &str, salt: &str) -> Self {
+        let offset = salt
+            .bytes()
+            .fold(0u64, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u64));
+
+        Self {
+            alphabet: alphabet.chars().collect(),
+            offset,
+        }
+    }
+
+    pub fn encode(&self, id: u64) -> String {
+        let base = self.alphabet.len() as u64;
+        let mut value = id ^ self.offset;
+        let mut chars = Vec::new();
+
+        loop {
+            chars.push(self.alphabet[(value % base) as usize]);
+            value /= base;
+            if value == 0 {
+                break;
+            }
+        }
+
+        chars.reverse();
+        chars.into_iter().collect()
+    }
+
+    pub fn decode(&self, encoded: &str) -> u64 {
+        let base = self.alphabet.len() as u64;
+        let mut value: u64 = 0;
+
+        for c in encoded.chars() {
+            let index = self.alphabet.iter().position(|a| *a == c).unwrap_or(0) as u64;
+            value = value * base + index;
+        }
+
+        value ^ self.offset
+    }
+}
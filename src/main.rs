@@ -2,30 +2,86 @@ extern crate core;
 
 use env_logger::Builder;
 use std::io::Write;
-use std::sync::Mutex;
+use std::sync::{Mutex, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use actix_files as fs;
 use actix_multipart::Multipart;
+use actix_web::dev::ServiceRequest;
+use actix_web::middleware::Condition;
 use actix_web::{get, web, App, Error, HttpResponse, HttpServer, Responder};
+use actix_web_httpauth::extractors::basic::BasicAuth;
+use actix_web_httpauth::middleware::HttpAuthentication;
 use askama::Template;
 use chrono::Local;
 use clap::Parser;
 use futures::TryStreamExt as _;
 use linkify::{LinkFinder, LinkKind};
 use log::LevelFilter;
+use qrcode::render::svg;
+use qrcode::QrCode;
 use rand::Rng;
+use subtle::ConstantTimeEq;
 
 use crate::animalnumbers::{to_animal_names, to_u64};
 use crate::dbio::save_to_file;
+use crate::hashids::HashId;
 use crate::pasta::Pasta;
+use crate::syntaxhighlighter::SyntaxHighlighter;
 
 mod animalnumbers;
 mod dbio;
+mod hashids;
 mod pasta;
+mod syntaxhighlighter;
+
+const HASHID_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ1234567890";
+const HASHID_SALT: &str = "microbin";
 
 struct AppState {
     pastas: Mutex<Vec<Pasta>>,
+    highlighter: SyntaxHighlighter,
+}
+
+/// Configured HTTP Basic Auth credentials, set once at startup from `Args`.
+/// `None` means the instance requires no authentication.
+static AUTH_CREDENTIALS: OnceLock<Option<(String, String)>> = OnceLock::new();
+
+/// Identifier encoding scheme chosen via `--id-scheme`, set once at startup.
+static ID_SCHEME: OnceLock<IdScheme> = OnceLock::new();
+static HASH_ID: OnceLock<HashId> = OnceLock::new();
+
+/// Base URL that the pasta links encoded in QR codes are generated against, set once
+/// at startup from `Args`.
+static PUBLIC_PATH: OnceLock<String> = OnceLock::new();
+
+/// Whether `--readonly` was passed, freezing the instance against new writes.
+static READONLY: OnceLock<bool> = OnceLock::new();
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+#[clap(rename_all = "lowercase")]
+enum IdScheme {
+    Animals,
+    Hashids,
+    U64,
+}
+
+/// Encodes a pasta id into a URL-facing string using the configured `--id-scheme`.
+fn encode_id(id: u64) -> String {
+    match ID_SCHEME.get().unwrap_or(&IdScheme::Animals) {
+        IdScheme::Animals => to_animal_names(id),
+        IdScheme::Hashids => HASH_ID.get().expect("hashids not initialized").encode(id),
+        IdScheme::U64 => id.to_string(),
+    }
+}
+
+/// Decodes a URL-facing id string back into a pasta id using the configured `--id-scheme`.
+fn decode_id(value: &str) -> u64 {
+    match ID_SCHEME.get().unwrap_or(&IdScheme::Animals) {
+        IdScheme::Animals => to_u64(value),
+        IdScheme::Hashids => HASH_ID.get().expect("hashids not initialized").decode(value),
+        IdScheme::U64 => value.parse().unwrap_or(0),
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -33,6 +89,58 @@ struct AppState {
 struct Args {
     #[clap(short, long, default_value_t = 8080)]
     port: u32,
+
+    /// Name of the syntect theme used to render highlighted text pastas.
+    #[clap(long, default_value = "InspiredGitHub")]
+    theme: String,
+
+    /// Require HTTP Basic Auth with this username for every route.
+    #[clap(long)]
+    auth_username: Option<String>,
+
+    /// Password to pair with `--auth-username` (empty if omitted).
+    #[clap(long)]
+    auth_password: Option<String>,
+
+    /// Identifier encoding scheme used for shareable pasta URLs.
+    #[clap(long, value_enum, default_value = "animals")]
+    id_scheme: IdScheme,
+
+    /// Base URL to generate shareable links against, e.g. when running behind a reverse proxy.
+    #[clap(long)]
+    public_path: Option<String>,
+
+    /// Freeze the instance: refuse new uploads and deletions.
+    #[clap(long)]
+    readonly: bool,
+}
+
+async fn validate_auth(
+    req: ServiceRequest,
+    credentials: BasicAuth,
+) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    let authorized = match AUTH_CREDENTIALS.get().and_then(|c| c.as_ref()) {
+        Some((username, password)) => {
+            constant_time_eq(credentials.user_id(), username)
+                && constant_time_eq(credentials.password().unwrap_or(""), password)
+        }
+        None => true,
+    };
+
+    if authorized {
+        Ok(req)
+    } else {
+        Err((
+            actix_web::error::ErrorUnauthorized("invalid credentials"),
+            req,
+        ))
+    }
+}
+
+/// Compares two strings in constant time so Basic Auth checks don't leak how
+/// many leading bytes matched via response timing.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
 }
 
 #[derive(Template)]
@@ -47,6 +155,7 @@ struct ErrorTemplate {}
 #[template(path = "pasta.html")]
 struct PastaTemplate<'a> {
     pasta: &'a Pasta,
+    highlighted: Option<String>,
 }
 
 #[derive(Template)]
@@ -55,6 +164,19 @@ struct PastaListTemplate<'a> {
     pastas: &'a Vec<Pasta>,
 }
 
+#[derive(Template)]
+#[template(path = "edit.html")]
+struct EditTemplate<'a> {
+    pasta: &'a Pasta,
+}
+
+#[derive(Template)]
+#[template(path = "qr.html")]
+struct QrTemplate {
+    url: String,
+    qr_svg: String,
+}
+
 #[get("/")]
 async fn index() -> impl Responder {
     HttpResponse::Found()
@@ -69,6 +191,12 @@ async fn not_found() -> Result<HttpResponse, Error> {
 }
 
 async fn create(data: web::Data<AppState>, mut payload: Multipart) -> Result<HttpResponse, Error> {
+    if *READONLY.get().unwrap_or(&false) {
+        return Ok(HttpResponse::Found()
+            .append_header(("Location", "/"))
+            .finish());
+    }
+
     let mut pastas = data.pastas.lock().unwrap();
 
     let timenow: i64 = match SystemTime::now().duration_since(UNIX_EPOCH) {
@@ -79,10 +207,16 @@ async fn create(data: web::Data<AppState>, mut payload: Multipart) -> Result<Htt
     let mut new_pasta = Pasta {
         id: rand::thread_rng().gen::<u16>() as u64,
         content: String::from("No Text Content"),
+        extension: String::from(""),
         file: String::from("no-file"),
         created: timenow,
         pasta_type: String::from(""),
         expiration: 0,
+        read_count: 0,
+        burn_after_reads: 0,
+        last_read: 0,
+        editable: false,
+        private: false,
     };
 
     while let Some(mut field) = payload.try_next().await? {
@@ -102,6 +236,31 @@ async fn create(data: web::Data<AppState>, mut payload: Multipart) -> Result<Htt
 
                 continue;
             }
+            "editable" => {
+                new_pasta.editable = true;
+                continue;
+            }
+            "private" => {
+                new_pasta.private = true;
+                continue;
+            }
+            "burn_after_reads" => {
+                while let Some(chunk) = field.try_next().await? {
+                    new_pasta.burn_after_reads = std::str::from_utf8(&chunk)
+                        .unwrap()
+                        .parse()
+                        .unwrap_or(0);
+                }
+
+                continue;
+            }
+            "extension" => {
+                while let Some(chunk) = field.try_next().await? {
+                    new_pasta.extension = std::str::from_utf8(&chunk).unwrap().to_string();
+                }
+
+                continue;
+            }
             "content" => {
                 while let Some(chunk) = field.try_next().await? {
                     new_pasta.content = std::str::from_utf8(&chunk).unwrap().to_string();
@@ -118,10 +277,16 @@ async fn create(data: web::Data<AppState>, mut payload: Multipart) -> Result<Htt
 
                 let filename = match content_disposition.get_filename() {
                     Some("") => continue,
-                    Some(filename) => filename.replace(' ', "_").to_string(),
+                    Some(filename) => sanitize_filename::sanitize(filename),
                     None => continue,
                 };
 
+                new_pasta.extension = std::path::Path::new(&filename)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .unwrap_or("")
+                    .to_string();
+
                 std::fs::create_dir_all(format!("./pasta_data/{}", &new_pasta.id_as_animals()))
                     .unwrap();
 
@@ -148,22 +313,47 @@ async fn create(data: web::Data<AppState>, mut payload: Multipart) -> Result<Htt
     save_to_file(&pastas);
 
     Ok(HttpResponse::Found()
-        .append_header(("Location", format!("/pasta/{}", to_animal_names(id))))
+        .append_header(("Location", format!("/pasta/{}", encode_id(id))))
         .finish())
 }
 
 #[get("/pasta/{id}")]
 async fn getpasta(data: web::Data<AppState>, id: web::Path<String>) -> HttpResponse {
     let mut pastas = data.pastas.lock().unwrap();
-    let id = to_u64(&*id.into_inner());
+    let id = decode_id(&id.into_inner());
 
     remove_expired(&mut pastas);
 
-    for pasta in pastas.iter() {
+    let timenow: i64 = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(n) => n.as_secs(),
+        Err(_) => panic!("SystemTime before UNIX EPOCH!"),
+    } as i64;
+
+    for (i, pasta) in pastas.iter().enumerate() {
         if pasta.id == id {
-            return HttpResponse::Found()
+            let read_count = pasta.read_count + 1;
+            let burn_after_reads = pasta.burn_after_reads;
+
+            let highlighted = if pasta.pasta_type == "text" {
+                Some(data.highlighter.highlight(&pasta.content, &pasta.extension))
+            } else {
+                None
+            };
+
+            let response = HttpResponse::Found()
                 .content_type("text/html")
-                .body(PastaTemplate { pasta }.render().unwrap());
+                .body(PastaTemplate { pasta, highlighted }.render().unwrap());
+
+            if burn_after_reads != 0 && read_count >= burn_after_reads {
+                delete_pasta_files(&pastas[i]);
+                pastas.remove(i);
+                save_to_file(&pastas);
+            } else {
+                pastas[i].read_count = read_count;
+                pastas[i].last_read = timenow;
+            }
+
+            return response;
         }
     }
 
@@ -175,7 +365,7 @@ async fn getpasta(data: web::Data<AppState>, id: web::Path<String>) -> HttpRespo
 #[get("/url/{id}")]
 async fn redirecturl(data: web::Data<AppState>, id: web::Path<String>) -> HttpResponse {
     let mut pastas = data.pastas.lock().unwrap();
-    let id = to_u64(&*id.into_inner());
+    let id = decode_id(&id.into_inner());
 
     remove_expired(&mut pastas);
 
@@ -198,13 +388,31 @@ async fn redirecturl(data: web::Data<AppState>, id: web::Path<String>) -> HttpRe
 async fn getrawpasta(data: web::Data<AppState>, id: web::Path<String>) -> String {
     let mut pastas = data.pastas.lock().unwrap();
 
-    let id = to_u64(&*id.into_inner());
+    let id = decode_id(&id.into_inner());
 
     remove_expired(&mut pastas);
 
-    for pasta in pastas.iter() {
+    let timenow: i64 = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(n) => n.as_secs(),
+        Err(_) => panic!("SystemTime before UNIX EPOCH!"),
+    } as i64;
+
+    for (i, pasta) in pastas.iter().enumerate() {
         if pasta.id == id {
-            return pasta.content.to_owned();
+            let content = pasta.content.to_owned();
+            let read_count = pasta.read_count + 1;
+            let burn_after_reads = pasta.burn_after_reads;
+
+            if burn_after_reads != 0 && read_count >= burn_after_reads {
+                delete_pasta_files(&pastas[i]);
+                pastas.remove(i);
+                save_to_file(&pastas);
+            } else {
+                pastas[i].read_count = read_count;
+                pastas[i].last_read = timenow;
+            }
+
+            return content;
         }
     }
 
@@ -213,14 +421,22 @@ async fn getrawpasta(data: web::Data<AppState>, id: web::Path<String>) -> String
 
 #[get("/remove/{id}")]
 async fn remove(data: web::Data<AppState>, id: web::Path<String>) -> HttpResponse {
+    if *READONLY.get().unwrap_or(&false) {
+        return HttpResponse::Found().append_header(("Location", "/")).finish();
+    }
+
     let mut pastas = data.pastas.lock().unwrap();
-    let id = to_u64(&*id.into_inner());
+    let id = decode_id(&id.into_inner());
 
     remove_expired(&mut pastas);
 
     for (i, pasta) in pastas.iter().enumerate() {
         if pasta.id == id {
+            delete_pasta_files(pasta);
+
             pastas.remove(i);
+            save_to_file(&pastas);
+
             return HttpResponse::Found()
                 .append_header(("Location", "/pastalist"))
                 .finish();
@@ -229,15 +445,121 @@ async fn remove(data: web::Data<AppState>, id: web::Path<String>) -> HttpRespons
     HttpResponse::Found().body("Pasta not found! :-(")
 }
 
+#[get("/edit/{id}")]
+async fn getedit(data: web::Data<AppState>, id: web::Path<String>) -> HttpResponse {
+    let mut pastas = data.pastas.lock().unwrap();
+    let id = decode_id(&id.into_inner());
+
+    remove_expired(&mut pastas);
+
+    for pasta in pastas.iter() {
+        if pasta.id == id {
+            if !pasta.editable {
+                return HttpResponse::Found()
+                    .content_type("text/html")
+                    .body(ErrorTemplate {}.render().unwrap());
+            }
+
+            return HttpResponse::Found()
+                .content_type("text/html")
+                .body(EditTemplate { pasta }.render().unwrap());
+        }
+    }
+
+    HttpResponse::Found()
+        .content_type("text/html")
+        .body(ErrorTemplate {}.render().unwrap())
+}
+
+async fn postedit(
+    data: web::Data<AppState>,
+    id: web::Path<String>,
+    mut payload: Multipart,
+) -> Result<HttpResponse, Error> {
+    if *READONLY.get().unwrap_or(&false) {
+        return Ok(HttpResponse::Found()
+            .append_header(("Location", "/"))
+            .finish());
+    }
+
+    let mut pastas = data.pastas.lock().unwrap();
+    let id = decode_id(&id.into_inner());
+
+    remove_expired(&mut pastas);
+
+    let pos = pastas.iter().position(|pasta| pasta.id == id);
+
+    let pos = match pos {
+        Some(pos) if pastas[pos].editable => pos,
+        _ => {
+            return Ok(HttpResponse::Found()
+                .content_type("text/html")
+                .body(ErrorTemplate {}.render().unwrap()))
+        }
+    };
+
+    while let Some(mut field) = payload.try_next().await? {
+        if field.name() == "content" {
+            while let Some(chunk) = field.try_next().await? {
+                pastas[pos].content = std::str::from_utf8(&chunk).unwrap().to_string();
+            }
+        }
+    }
+
+    pastas[pos].pasta_type = if is_valid_url(pastas[pos].content.as_str()) {
+        String::from("url")
+    } else {
+        String::from("text")
+    };
+
+    save_to_file(&pastas);
+
+    Ok(HttpResponse::Found()
+        .append_header(("Location", format!("/pasta/{}", encode_id(id))))
+        .finish())
+}
+
+#[get("/qr/{id}")]
+async fn getqr(data: web::Data<AppState>, id: web::Path<String>) -> HttpResponse {
+    let mut pastas = data.pastas.lock().unwrap();
+    let id = decode_id(&id.into_inner());
+
+    remove_expired(&mut pastas);
+
+    for pasta in pastas.iter() {
+        if pasta.id == id {
+            let url = format!(
+                "{}/pasta/{}",
+                PUBLIC_PATH.get().expect("public path not initialized"),
+                encode_id(pasta.id)
+            );
+
+            let qr_svg = QrCode::new(url.as_bytes())
+                .map(|code| code.render::<svg::Color>().min_dimensions(200, 200).build())
+                .unwrap_or_default();
+
+            return HttpResponse::Found()
+                .content_type("text/html")
+                .body(QrTemplate { url, qr_svg }.render().unwrap());
+        }
+    }
+
+    HttpResponse::Found()
+        .content_type("text/html")
+        .body(ErrorTemplate {}.render().unwrap())
+}
+
 #[get("/pastalist")]
 async fn list(data: web::Data<AppState>) -> HttpResponse {
     let mut pastas = data.pastas.lock().unwrap();
 
     remove_expired(&mut pastas);
 
+    let listed: Vec<Pasta> = pastas.iter().filter(|p| !p.private).cloned().collect();
+
     HttpResponse::Found()
         .content_type("text/html")
-        .body(PastaListTemplate { pastas: &pastas }.render().unwrap())
+        .body(PastaListTemplate { pastas: &listed }.render().unwrap())
 }
 
 #[actix_web::main]
@@ -264,12 +586,40 @@ async fn main() -> std::io::Result<()> {
 
     std::fs::create_dir_all("./pasta_data").unwrap();
 
+    AUTH_CREDENTIALS
+        .set(
+            args.auth_username
+                .clone()
+                .map(|username| (username, args.auth_password.clone().unwrap_or_default())),
+        )
+        .ok();
+
+    ID_SCHEME.set(args.id_scheme.clone()).ok();
+    HASH_ID.set(HashId::new(HASHID_ALPHABET, HASHID_SALT)).ok();
+
+    PUBLIC_PATH
+        .set(
+            args.public_path
+                .clone()
+                .unwrap_or_else(|| format!("http://127.0.0.1:{}", args.port)),
+        )
+        .ok();
+
+    READONLY.set(args.readonly).ok();
+
     let data = web::Data::new(AppState {
         pastas: Mutex::new(dbio::load_from_file().unwrap()),
+        highlighter: SyntaxHighlighter::new(args.theme.clone()),
     });
 
+    let auth_enabled = args.auth_username.is_some();
+
     HttpServer::new(move || {
         App::new()
+            .wrap(Condition::new(
+                auth_enabled,
+                HttpAuthentication::basic(validate_auth),
+            ))
             .app_data(data.clone())
             .service(index)
             .service(getpasta)
@@ -277,9 +627,12 @@ async fn main() -> std::io::Result<()> {
             .service(getrawpasta)
             .service(remove)
             .service(list)
+            .service(getedit)
+            .service(getqr)
             .service(fs::Files::new("/static", "./static"))
             .service(fs::Files::new("/file", "./pasta_data"))
             .service(web::resource("/upload").route(web::post().to(create)))
+            .service(web::resource("/edit/{id}").route(web::post().to(postedit)))
             .default_service(web::route().to(not_found))
     })
     .bind(format!("127.0.0.1:{}", args.port.to_string()))?
@@ -293,7 +646,29 @@ fn remove_expired(pastas: &mut Vec<Pasta>) {
         Err(_) => panic!("SystemTime before UNIX EPOCH!"),
     } as i64;
 
+    let expired: Vec<&Pasta> = pastas
+        .iter()
+        .filter(|p| p.expiration != 0 && p.expiration <= timenow)
+        .collect();
+
+    if expired.is_empty() {
+        return;
+    }
+
+    for pasta in expired {
+        delete_pasta_files(pasta);
+    }
+
     pastas.retain(|p| p.expiration == 0 || p.expiration > timenow);
+
+    save_to_file(pastas);
+}
+
+/// Removes a pasta's uploaded file directory from disk, if it has one.
+fn delete_pasta_files(pasta: &Pasta) {
+    if pasta.file != "no-file" {
+        let _ = std::fs::remove_dir_all(format!("./pasta_data/{}", pasta.id_as_animals()));
+    }
 }
 
 fn is_valid_url(url: &str) -> bool {
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+use crate::animalnumbers::to_animal_names;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Pasta {
+    pub id: u64,
+    pub content: String,
+    #[serde(default)]
+    pub extension: String,
+    pub file: String,
+    pub created: i64,
+    pub pasta_type: String,
+    pub expiration: i64,
+    #[serde(default)]
+    pub read_count: u64,
+    #[serde(default)]
+    pub burn_after_reads: u64,
+    #[serde(default)]
+    pub last_read: i64,
+    #[serde(default)]
+    pub editable: bool,
+    #[serde(default)]
+    pub private: bool,
+}
+
+impl Pasta {
+    pub fn id_as_animals(&self) -> String {
+        to_animal_names(self.id)
+    }
+}
@@ -0,0 +1,50 @@
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::SyntaxSet;
+
+/// Wraps the `syntect` default syntax/theme sets so they're only loaded once at startup.
+pub struct SyntaxHighlighter {
+    syntax_set: SyntaxSet,
+    theme_set: ThemeSet,
+    theme_name: String,
+}
+
+impl SyntaxHighlighter {
+    pub fn new(theme_name: String) -> Self {
+        Self {
+            syntax_set: SyntaxSet::load_defaults_newlines(),
+            theme_set: ThemeSet::load_defaults(),
+            theme_name,
+        }
+    }
+
+    fn theme(&self) -> &Theme {
+        self.theme_set
+            .themes
+            .get(&self.theme_name)
+            .unwrap_or_else(|| &self.theme_set.themes["InspiredGitHub"])
+    }
+
+    /// Renders `content` as colorized `<pre>`-wrapped HTML, picking a syntax by file
+    /// extension and falling back to a first-line heuristic, then plain text.
+    pub fn highlight(&self, content: &str, extension: &str) -> String {
+        let syntax = self
+            .syntax_set
+            .find_syntax_by_extension(extension)
+            .or_else(|| self.syntax_set.find_syntax_by_first_line(content))
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+
+        highlighted_html_for_string(content, &self.syntax_set, syntax, self.theme())
+            .unwrap_or_else(|_| format!("<pre>{}</pre>", escape_html(content)))
+    }
+}
+
+/// Escapes text for safe inclusion in HTML. Used as the fallback when syntect's
+/// highlighter errors out, since its success path already escapes its output.
+fn escape_html(content: &str) -> String {
+    content
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}